@@ -0,0 +1,130 @@
+use futures::future::BoxFuture;
+use http::header::{HeaderValue, ACCESS_CONTROL_REQUEST_HEADERS, ORIGIN, VARY};
+use http::HeaderName;
+use tide::middleware::{Middleware, Next};
+use tide::{Context, Response};
+
+const ACCESS_CONTROL_ALLOW_ORIGIN: &str = "access-control-allow-origin";
+const ACCESS_CONTROL_ALLOW_METHODS: &str = "access-control-allow-methods";
+const ACCESS_CONTROL_ALLOW_HEADERS: &str = "access-control-allow-headers";
+
+/// Headers reflected when the preflight request didn't list any `Access-Control-Request-Headers`
+/// itself. `X-Meili-API-Key` is required by virtually every route, so without it a browser
+/// preflight for any non-simple cross-origin request is rejected no matter how origins/methods
+/// are configured.
+const DEFAULT_ALLOW_HEADERS: &str = "X-Meili-API-Key, Content-Type";
+
+/// CORS middleware that reflects a single request `Origin` back in
+/// `Access-Control-Allow-Origin`, which is the only way to legally allow more than one origin
+/// (the header can carry a single origin or `*`, never a list). `allowed_origins: None` means
+/// any origin is allowed and is reflected as-is; `Some(origins)` only reflects origins present
+/// in the list.
+pub struct Cors {
+    allowed_origins: Option<Vec<String>>,
+    allowed_methods: HeaderValue,
+}
+
+impl Cors {
+    pub fn new(allowed_origins: Option<Vec<String>>, allowed_methods: String) -> Cors {
+        Cors {
+            allowed_origins,
+            allowed_methods: HeaderValue::from_str(&allowed_methods)
+                .unwrap_or_else(|_| HeaderValue::from_static("GET, POST, PUT, DELETE, OPTIONS")),
+        }
+    }
+
+    fn allowed_origin(&self, origin: &str) -> bool {
+        match &self.allowed_origins {
+            None => true,
+            Some(origins) => origins.iter().any(|allowed| allowed == origin),
+        }
+    }
+}
+
+/// The `Access-Control-Allow-Headers` value: the browser tells us, via
+/// `Access-Control-Request-Headers`, exactly which headers the actual request will carry, so we
+/// reflect those back; when it doesn't (a non-preflight CORS response), fall back to the
+/// headers this API actually expects clients to send.
+fn allow_headers(requested_headers: Option<&str>) -> String {
+    match requested_headers {
+        Some(headers) if !headers.trim().is_empty() => headers.to_string(),
+        _ => DEFAULT_ALLOW_HEADERS.to_string(),
+    }
+}
+
+impl<State: Send + Sync + 'static> Middleware<State> for Cors {
+    fn handle<'a>(&'a self, ctx: Context<State>, next: Next<'a, State>) -> BoxFuture<'a, Response> {
+        Box::pin(async move {
+            let origin = ctx
+                .headers()
+                .get(ORIGIN)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+
+            let requested_headers = ctx
+                .headers()
+                .get(ACCESS_CONTROL_REQUEST_HEADERS)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+
+            let mut response = next.run(ctx).await;
+
+            if let Some(origin) = origin {
+                if self.allowed_origin(&origin) {
+                    if let Ok(allow_origin) = HeaderValue::from_str(&origin) {
+                        let allow_headers = allow_headers(requested_headers.as_deref());
+
+                        if let Ok(allow_headers) = HeaderValue::from_str(&allow_headers) {
+                            let headers = response.headers_mut();
+                            headers.insert(
+                                HeaderName::from_static(ACCESS_CONTROL_ALLOW_ORIGIN),
+                                allow_origin,
+                            );
+                            headers.insert(VARY, HeaderValue::from_static("Origin"));
+                            headers.insert(
+                                HeaderName::from_static(ACCESS_CONTROL_ALLOW_METHODS),
+                                self.allowed_methods.clone(),
+                            );
+                            headers.insert(
+                                HeaderName::from_static(ACCESS_CONTROL_ALLOW_HEADERS),
+                                allow_headers,
+                            );
+                        }
+                    }
+                }
+            }
+
+            response
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn any_origin_allowed_when_unrestricted() {
+        let cors = Cors::new(None, "GET".to_string());
+
+        assert!(cors.allowed_origin("https://example.com"));
+    }
+
+    #[test]
+    fn only_listed_origins_allowed_when_restricted() {
+        let cors = Cors::new(Some(vec!["https://example.com".to_string()]), "GET".to_string());
+
+        assert!(cors.allowed_origin("https://example.com"));
+        assert!(!cors.allowed_origin("https://evil.example"));
+    }
+
+    #[test]
+    fn preflight_reflects_requested_headers() {
+        assert_eq!(allow_headers(Some("X-Custom-Header")), "X-Custom-Header");
+    }
+
+    #[test]
+    fn preflight_falls_back_to_default_headers_when_none_requested() {
+        assert_eq!(allow_headers(None), DEFAULT_ALLOW_HEADERS);
+    }
+}
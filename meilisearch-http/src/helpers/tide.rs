@@ -1,54 +1,250 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use heed::types::{SerdeBincode, Str};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tide::Context;
+
 use crate::error::{ResponseError, SResult};
 use crate::Data;
 use meilisearch_core::Index;
-use tide::Context;
+
+const KEYS_INDEX_KEY: &str = "_api_keys_index";
+const KEY_STORE_PREFIX: &str = "_api_key_";
 
 pub trait ContextExt {
-    fn is_allowed(&self, acl: ACL) -> SResult<()>;
+    fn is_allowed(&self, action: Action) -> SResult<()>;
+    fn is_master(&self) -> SResult<()>;
     fn header(&self, name: &str) -> Result<String, ResponseError>;
     fn url_param(&self, name: &str) -> Result<String, ResponseError>;
     fn index(&self) -> Result<Index, ResponseError>;
     fn identifier(&self) -> Result<String, ResponseError>;
 }
 
-pub enum ACL {
-    Admin,
-    Private,
-    Public
+/// The fine-grained permissions a key can be granted. Adding a new protected route means
+/// adding a variant here and checking it with `ContextExt::is_allowed` in that route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Search,
+    DocumentsGet,
+    DocumentsAdd,
+    DocumentsDelete,
+    SettingsGet,
+    SettingsUpdate,
+}
+
+/// A scoped, revocable API key minted through `POST /keys` and persisted in the
+/// `common_store` alongside the rest of Meilisearch's metadata.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKey {
+    pub key: String,
+    pub description: Option<String>,
+    pub actions: Vec<Action>,
+    /// `None` means the key is not restricted to a subset of indexes.
+    pub indexes: Option<Vec<String>>,
+    /// Unix timestamp, in seconds, after which the key stops being accepted.
+    pub expires_at: Option<u64>,
+    pub created_at: u64,
+}
+
+impl ApiKey {
+    pub fn generate(description: Option<String>, actions: Vec<Action>, indexes: Option<Vec<String>>, expires_at: Option<u64>) -> ApiKey {
+        let mut bytes = [0u8; 24];
+        rand::thread_rng().fill(&mut bytes);
+
+        ApiKey {
+            key: format!("{}-{}", "meili", hex::encode(bytes)),
+            description,
+            actions,
+            indexes,
+            expires_at,
+            created_at: now(),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        match self.expires_at {
+            Some(expires_at) => now() >= expires_at,
+            None => false,
+        }
+    }
+
+    pub fn allows(&self, action: Action, index_uid: Option<&str>) -> bool {
+        if self.is_expired() || !self.actions.contains(&action) {
+            return false;
+        }
+
+        match (&self.indexes, index_uid) {
+            (Some(indexes), Some(index_uid)) => indexes.iter().any(|uid| uid == index_uid),
+            _ => true,
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+fn store_key_for(key: &str) -> String {
+    format!("{}{}", KEY_STORE_PREFIX, key)
+}
+
+pub fn save_key(data: &Data, writer: &mut heed::RwTxn, key: &ApiKey) -> SResult<()> {
+    let common_store = data.db.common_store();
+
+    let mut known_keys = list_keys(data, writer)?;
+    if !known_keys.contains(&key.key) {
+        known_keys.push(key.key.clone());
+        common_store
+            .put::<_, Str, SerdeBincode<Vec<String>>>(writer, KEYS_INDEX_KEY, &known_keys)
+            .map_err(ResponseError::internal)?;
+    }
+
+    common_store
+        .put::<_, Str, SerdeBincode<ApiKey>>(writer, &store_key_for(&key.key), key)
+        .map_err(ResponseError::internal)?;
+
+    Ok(())
+}
+
+pub fn list_keys(data: &Data, writer: &mut heed::RwTxn) -> SResult<Vec<String>> {
+    let common_store = data.db.common_store();
+    let known_keys = common_store
+        .get::<_, Str, SerdeBincode<Vec<String>>>(writer, KEYS_INDEX_KEY)
+        .map_err(ResponseError::internal)?
+        .unwrap_or_default();
+    Ok(known_keys)
+}
+
+pub fn get_key(reader: &heed::RoTxn, data: &Data, key: &str) -> SResult<Option<ApiKey>> {
+    let common_store = data.db.common_store();
+    common_store
+        .get::<_, Str, SerdeBincode<ApiKey>>(reader, &store_key_for(key))
+        .map_err(ResponseError::internal)
+}
+
+pub fn delete_key(data: &Data, writer: &mut heed::RwTxn, key: &str) -> SResult<()> {
+    let common_store = data.db.common_store();
+
+    let known_keys: Vec<String> = list_keys(data, writer)?
+        .into_iter()
+        .filter(|known_key| known_key != key)
+        .collect();
+    common_store
+        .put::<_, Str, SerdeBincode<Vec<String>>>(writer, KEYS_INDEX_KEY, &known_keys)
+        .map_err(ResponseError::internal)?;
+
+    common_store
+        .delete::<_, Str>(writer, &store_key_for(key))
+        .map_err(ResponseError::internal)?;
+
+    Ok(())
+}
+
+pub fn all_keys(reader: &heed::RoTxn, data: &Data) -> SResult<Vec<ApiKey>> {
+    let common_store = data.db.common_store();
+    let known_keys = common_store
+        .get::<_, Str, SerdeBincode<Vec<String>>>(reader, KEYS_INDEX_KEY)
+        .map_err(ResponseError::internal)?
+        .unwrap_or_default();
+
+    known_keys
+        .into_iter()
+        .filter_map(|key| get_key(reader, data, &key).transpose())
+        .collect()
+}
+
+/// The actions granted to the legacy `MEILI_MASTER_KEY`-derived private/public keys, kept so
+/// that existing deployments don't lose access when upgrading to the scoped key store.
+const LEGACY_PUBLIC_ACTIONS: &[Action] = &[Action::Search, Action::DocumentsGet];
+const LEGACY_PRIVATE_ACTIONS: &[Action] = &[
+    Action::Search,
+    Action::DocumentsGet,
+    Action::DocumentsAdd,
+    Action::DocumentsDelete,
+    Action::SettingsGet,
+    Action::SettingsUpdate,
+];
+
+fn legacy_api_key(key: &str, actions: &[Action]) -> ApiKey {
+    ApiKey {
+        key: key.to_string(),
+        description: Some("Migrated from the legacy private/public key".to_string()),
+        actions: actions.to_vec(),
+        indexes: None,
+        expires_at: None,
+        created_at: now(),
+    }
+}
+
+/// Seeds the scoped key store with the legacy private/public keys on startup, so `is_allowed`
+/// keeps accepting them after upgrading from the old three-key authorization model. Only
+/// missing keys are seeded: once a legacy key is migrated, it becomes a regular entry in the
+/// store (revocable, editable externally) and subsequent restarts must not clobber it back to
+/// its original `created_at`/defaults.
+pub fn migrate_legacy_keys(data: &Data) -> SResult<()> {
+    let reader = data.db.main_read_txn().map_err(ResponseError::internal)?;
+
+    let mut missing = Vec::new();
+    if let Some(public_key) = &data.api_keys.public {
+        if get_key(&reader, data, public_key)?.is_none() {
+            missing.push(legacy_api_key(public_key, LEGACY_PUBLIC_ACTIONS));
+        }
+    }
+    if let Some(private_key) = &data.api_keys.private {
+        if get_key(&reader, data, private_key)?.is_none() {
+            missing.push(legacy_api_key(private_key, LEGACY_PRIVATE_ACTIONS));
+        }
+    }
+    drop(reader);
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    let mut writer = data.db.main_write_txn().map_err(ResponseError::internal)?;
+    for key in &missing {
+        save_key(data, &mut writer, key)?;
+    }
+    writer.commit().map_err(ResponseError::internal)?;
+
+    Ok(())
 }
 
 impl ContextExt for Context<Data> {
-    fn is_allowed(&self, acl: ACL) -> SResult<()> {
+    fn is_allowed(&self, action: Action) -> SResult<()> {
         let user_api_key = self.header("X-Meili-API-Key")?;
 
-        match acl {
-            ACL::Admin => {
-                if Some(user_api_key.clone()) == self.state().api_keys.master {
-                    return Ok(())
-                }
-            },
-            ACL::Private => {
-                if Some(user_api_key.clone()) == self.state().api_keys.master {
-                    return Ok(())
-                }
-                if Some(user_api_key.clone()) == self.state().api_keys.private {
-                    return Ok(())
-                }
-            },
-            ACL::Public => {
-                if Some(user_api_key.clone()) == self.state().api_keys.master {
-                    return Ok(())
-                }
-                if Some(user_api_key.clone()) == self.state().api_keys.private {
-                    return Ok(())
-                }
-                if Some(user_api_key.clone()) == self.state().api_keys.public {
-                    return Ok(())
-                }
-            }
+        if Some(user_api_key.clone()) == self.state().api_keys.master {
+            return Ok(());
         }
 
-        Err(ResponseError::InvalidToken(user_api_key.to_string()))
+        let reader = self.state().db.main_read_txn().map_err(ResponseError::internal)?;
+        let key = get_key(&reader, self.state(), &user_api_key)?
+            .ok_or_else(|| ResponseError::InvalidToken(user_api_key.clone()))?;
+
+        let index_uid = self.url_param("index").ok();
+
+        if key.allows(action, index_uid.as_deref()) {
+            Ok(())
+        } else {
+            Err(ResponseError::InvalidToken(user_api_key))
+        }
+    }
+
+    fn is_master(&self) -> SResult<()> {
+        let user_api_key = self.header("X-Meili-API-Key")?;
+
+        if Some(user_api_key.clone()) == self.state().api_keys.master {
+            Ok(())
+        } else {
+            Err(ResponseError::InvalidToken(user_api_key))
+        }
     }
 
     fn header(&self, name: &str) -> Result<String, ResponseError> {
@@ -87,3 +283,73 @@ impl ContextExt for Context<Data> {
         Ok(name)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_with(actions: Vec<Action>, indexes: Option<Vec<String>>, expires_at: Option<u64>) -> ApiKey {
+        ApiKey {
+            key: "test-key".to_string(),
+            description: None,
+            actions,
+            indexes,
+            expires_at,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn allows_when_action_and_index_match() {
+        let key = key_with(
+            vec![Action::Search],
+            Some(vec!["movies".to_string()]),
+            None,
+        );
+
+        assert!(key.allows(Action::Search, Some("movies")));
+    }
+
+    #[test]
+    fn denies_unknown_action() {
+        let key = key_with(vec![Action::Search], None, None);
+
+        assert!(!key.allows(Action::DocumentsAdd, None));
+    }
+
+    #[test]
+    fn denies_index_outside_restriction() {
+        let key = key_with(
+            vec![Action::Search],
+            Some(vec!["movies".to_string()]),
+            None,
+        );
+
+        assert!(!key.allows(Action::Search, Some("books")));
+    }
+
+    #[test]
+    fn unscoped_key_allows_any_index() {
+        let key = key_with(vec![Action::Search], None, None);
+
+        assert!(key.allows(Action::Search, Some("movies")));
+        assert!(key.allows(Action::Search, None));
+    }
+
+    #[test]
+    fn denies_expired_key() {
+        let key = key_with(vec![Action::Search], None, Some(1));
+
+        assert!(key.is_expired());
+        assert!(!key.allows(Action::Search, None));
+    }
+
+    #[test]
+    fn allows_key_that_has_not_expired_yet() {
+        let far_future = now() + 3600;
+        let key = key_with(vec![Action::Search], None, Some(far_future));
+
+        assert!(!key.is_expired());
+        assert!(key.allows(Action::Search, None));
+    }
+}
@@ -1,14 +1,14 @@
 use std::env::VarError::NotPresent;
 use std::{env, thread};
 
-use http::header::HeaderValue;
 use log::info;
 use main_error::MainError;
 use structopt::StructOpt;
-use tide::middleware::{CorsMiddleware, CorsOrigin};
 use tide_log::RequestLogger;
 
 use meilisearch_http::data::Data;
+use meilisearch_http::helpers::cors::Cors;
+use meilisearch_http::helpers::tide::migrate_legacy_keys;
 use meilisearch_http::option::Opt;
 use meilisearch_http::routes;
 use meilisearch_http::routes::index::index_update_callback;
@@ -39,6 +39,9 @@ pub fn main() -> Result<(), MainError> {
 
     let data = Data::new(opt.clone());
 
+    migrate_legacy_keys(&data)
+        .expect("failed to migrate the legacy private/public keys into the scoped key store");
+
     let data_cloned = data.clone();
     data.db.set_update_callback(Box::new(move |name, status| {
         index_update_callback(name, &data_cloned, status);
@@ -46,11 +49,10 @@ pub fn main() -> Result<(), MainError> {
 
     let mut app = tide::App::with_state(data.clone());
 
-    app.middleware(
-        CorsMiddleware::new()
-            .allow_origin(CorsOrigin::from("*"))
-            .allow_methods(HeaderValue::from_static("GET, POST, OPTIONS")),
-    );
+    app.middleware(Cors::new(
+        opt.cors_allowed_origins_list(),
+        opt.cors_allowed_methods(),
+    ));
     app.middleware(RequestLogger::new());
     app.middleware(tide_compression::Compression::new());
     app.middleware(tide_compression::Decompression::new());
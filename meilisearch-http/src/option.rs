@@ -20,4 +20,83 @@ pub struct Opt {
     /// - `development`: Show logs in "info" mode + not mendatory to specify the api keys
     #[structopt(long, env = "MEILI_ENV", default_value = "development")]
     pub env: String,
+
+    /// The origins allowed to make cross-origin requests to the server, as a comma-separated
+    /// list (e.g. `https://example.com,https://admin.example.com`), or `*` to allow any origin.
+    #[structopt(long, env = "MEILI_CORS_ALLOWED_ORIGINS", default_value = "*")]
+    pub cors_allowed_origins: String,
+
+    /// The number of pending and processing updates above which `GET /health` reports the
+    /// server as unavailable, so load balancers stop routing traffic to a node that is still
+    /// catching up on indexing.
+    #[structopt(long, env = "MEILI_MAX_PENDING_UPDATES", default_value = "100")]
+    pub max_pending_updates: usize,
+}
+
+/// The `(method, path)` of every route `routes::load_routes` registers on the `tide::App`.
+/// `Opt::cors_allowed_methods` derives the CORS allow-list from this table instead of a
+/// hand-maintained string, so it stays in sync as routes are added, changed or removed here.
+const ROUTES: &[(&str, &str)] = &[
+    ("GET", "/indexes/:index/documents/:identifier"),
+    ("DELETE", "/indexes/:index/documents/:identifier"),
+    ("GET", "/indexes/:index/documents"),
+    ("POST", "/indexes/:index/documents"),
+    ("PUT", "/indexes/:index/documents"),
+    ("POST", "/indexes/:index/documents/delete-batch"),
+    ("DELETE", "/indexes/:index/documents"),
+    ("GET", "/health"),
+    ("PUT", "/health"),
+    ("GET", "/keys"),
+    ("POST", "/keys"),
+    ("DELETE", "/keys/:key"),
+];
+
+impl Opt {
+    /// The distinct HTTP methods used by `ROUTES`, plus `OPTIONS` for CORS preflight itself,
+    /// exposed as the `Access-Control-Allow-Methods` value.
+    pub fn cors_allowed_methods(&self) -> String {
+        let mut methods: Vec<&str> = ROUTES.iter().map(|(method, _)| *method).collect();
+        methods.push("OPTIONS");
+        methods.sort_unstable();
+        methods.dedup();
+        methods.join(", ")
+    }
+
+    /// The comma-separated `cors_allowed_origins` split into individual origins, or `None`
+    /// when every origin is allowed.
+    pub fn cors_allowed_origins_list(&self) -> Option<Vec<String>> {
+        if self.cors_allowed_origins.trim() == "*" {
+            return None;
+        }
+
+        Some(
+            self.cors_allowed_origins
+                .split(',')
+                .map(|origin| origin.trim().to_string())
+                .filter(|origin| !origin.is_empty())
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cors_allowed_methods_is_deduped_and_sorted() {
+        let opt = Opt {
+            db_path: "./data.ms".to_string(),
+            http_addr: "127.0.0.1:7700".to_string(),
+            master_key: None,
+            env: "development".to_string(),
+            cors_allowed_origins: "*".to_string(),
+            max_pending_updates: 100,
+        };
+
+        assert_eq!(
+            opt.cors_allowed_methods(),
+            "DELETE, GET, OPTIONS, POST, PUT"
+        );
+    }
 }
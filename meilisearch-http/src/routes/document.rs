@@ -1,4 +1,5 @@
 use std::collections::{BTreeSet, HashSet};
+use std::str::FromStr;
 
 use http::StatusCode;
 use indexmap::IndexMap;
@@ -9,12 +10,12 @@ use tide::response::IntoResponse;
 use tide::{Context, Response};
 
 use crate::error::{ResponseError, SResult};
+use crate::helpers::tide::Action;
 use crate::helpers::tide::ContextExt;
-use crate::helpers::tide::ACL::*;
 use crate::Data;
 
 pub async fn get_document(ctx: Context<Data>) -> SResult<Response> {
-    ctx.is_allowed(Public)?;
+    ctx.is_allowed(Action::DocumentsGet)?;
 
     let index = ctx.index()?;
 
@@ -43,7 +44,7 @@ pub struct IndexUpdateResponse {
 }
 
 pub async fn delete_document(ctx: Context<Data>) -> SResult<Response> {
-    ctx.is_allowed(Private)?;
+    ctx.is_allowed(Action::DocumentsDelete)?;
 
     let index = ctx.index()?;
     let identifier = ctx.identifier()?;
@@ -75,7 +76,7 @@ struct BrowseQuery {
 }
 
 pub async fn get_all_documents(ctx: Context<Data>) -> SResult<Response> {
-    ctx.is_allowed(Public)?;
+    ctx.is_allowed(Action::DocumentsGet)?;
 
     let index = ctx.index()?;
     let query: BrowseQuery = ctx.url_query().unwrap_or(BrowseQuery::default());
@@ -117,6 +118,86 @@ pub async fn get_all_documents(ctx: Context<Data>) -> SResult<Response> {
     Ok(tide::response::json(response_body))
 }
 
+const CONTENT_TYPE_CSV: &str = "text/csv";
+const CONTENT_TYPE_NDJSON: &str = "application/x-ndjson";
+
+/// A CSV header cell can carry a `name:type` hint (e.g. `price:number`) telling us how to
+/// coerce that column's cells; columns without a hint fall back to sniffing the value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CsvFieldType {
+    String,
+    Number,
+    Boolean,
+    /// No hint in the header cell.
+    Auto,
+}
+
+fn parse_csv_header(header: &csv::StringRecord) -> Vec<(String, CsvFieldType)> {
+    header
+        .iter()
+        .map(|cell| {
+            // Only strip the suffix when it's a type hint we recognize; a column name that
+            // merely contains a colon (e.g. `og:title`) must be kept verbatim.
+            match cell.rfind(':').map(|idx| (&cell[..idx], &cell[idx + 1..])) {
+                Some((name, "number")) => (name.to_string(), CsvFieldType::Number),
+                Some((name, "boolean")) => (name.to_string(), CsvFieldType::Boolean),
+                Some((name, "string")) => (name.to_string(), CsvFieldType::String),
+                _ => (cell.to_string(), CsvFieldType::Auto),
+            }
+        })
+        .collect()
+}
+
+fn csv_cell_to_value(cell: &str, field_type: CsvFieldType) -> Value {
+    if cell.is_empty() {
+        return Value::Null;
+    }
+
+    match field_type {
+        CsvFieldType::Number => serde_json::Number::from_str(cell)
+            .map(Value::Number)
+            .unwrap_or_else(|_| Value::String(cell.to_string())),
+        CsvFieldType::Boolean => cell
+            .parse::<bool>()
+            .map(Value::Bool)
+            .unwrap_or_else(|_| Value::String(cell.to_string())),
+        CsvFieldType::String => Value::String(cell.to_string()),
+        // No explicit hint: sniff numbers so plain CSV still behaves sensibly.
+        CsvFieldType::Auto => serde_json::Number::from_str(cell)
+            .map(Value::Number)
+            .unwrap_or_else(|_| Value::String(cell.to_string())),
+    }
+}
+
+fn documents_from_csv(body: &str) -> Result<Vec<IndexMap<String, Value>>, ResponseError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_reader(body.as_bytes());
+
+    let fields = parse_csv_header(reader.headers().map_err(ResponseError::bad_request)?);
+
+    let mut documents = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(ResponseError::bad_request)?;
+        let document = fields
+            .iter()
+            .zip(record.iter())
+            .map(|((name, field_type), cell)| (name.clone(), csv_cell_to_value(cell, *field_type)))
+            .collect();
+        documents.push(document);
+    }
+
+    Ok(documents)
+}
+
+fn documents_from_ndjson(body: &str) -> Result<Vec<IndexMap<String, Value>>, ResponseError> {
+    body.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| serde_json::from_str(line).map_err(ResponseError::bad_request))
+        .collect()
+}
+
 fn infered_schema(document: &IndexMap<String, Value>) -> Option<meilisearch_schema::Schema> {
     use meilisearch_schema::{SchemaBuilder, DISPLAYED, INDEXED};
 
@@ -140,10 +221,25 @@ fn infered_schema(document: &IndexMap<String, Value>) -> Option<meilisearch_sche
 }
 
 async fn update_multiple_documents(mut ctx: Context<Data>, is_partial: bool) -> SResult<Response> {
-    ctx.is_allowed(Private)?;
+    ctx.is_allowed(Action::DocumentsAdd)?;
+
+    let content_type = ctx
+        .headers()
+        .get("Content-Type")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("application/json")
+        .to_string();
+
+    let data: Vec<IndexMap<String, Value>> = if content_type.starts_with(CONTENT_TYPE_CSV) {
+        let body = ctx.body_string().await.map_err(ResponseError::bad_request)?;
+        documents_from_csv(&body)?
+    } else if content_type.starts_with(CONTENT_TYPE_NDJSON) {
+        let body = ctx.body_string().await.map_err(ResponseError::bad_request)?;
+        documents_from_ndjson(&body)?
+    } else {
+        ctx.body_json().await.map_err(ResponseError::bad_request)?
+    };
 
-    let data: Vec<IndexMap<String, Value>> =
-        ctx.body_json().await.map_err(ResponseError::bad_request)?;
     let index = ctx.index()?;
 
     let db = &ctx.state().db;
@@ -196,7 +292,7 @@ pub async fn add_or_update_multiple_documents(ctx: Context<Data>) -> SResult<Res
 }
 
 pub async fn delete_multiple_documents(mut ctx: Context<Data>) -> SResult<Response> {
-    ctx.is_allowed(Private)?;
+    ctx.is_allowed(Action::DocumentsDelete)?;
 
     let data: Vec<Value> = ctx.body_json().await.map_err(ResponseError::bad_request)?;
     let index = ctx.index()?;
@@ -226,7 +322,7 @@ pub async fn delete_multiple_documents(mut ctx: Context<Data>) -> SResult<Respon
 }
 
 pub async fn clear_all_documents(ctx: Context<Data>) -> SResult<Response> {
-    ctx.is_allowed(Private)?;
+    ctx.is_allowed(Action::DocumentsDelete)?;
 
     let index = ctx.index()?;
 
@@ -243,3 +339,72 @@ pub async fn clear_all_documents(ctx: Context<Data>) -> SResult<Response> {
         .with_status(StatusCode::ACCEPTED)
         .into_response())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_parses_header_type_hints_and_sniffs_plain_columns() {
+        let body = "name,price:number,in_stock:boolean\nShirt,19.99,true\nMug,5,false\n";
+
+        let documents = documents_from_csv(body).unwrap();
+
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0]["name"], Value::String("Shirt".to_string()));
+        assert_eq!(documents[0]["price"], serde_json::json!(19.99));
+        assert_eq!(documents[0]["in_stock"], Value::Bool(true));
+        assert_eq!(documents[1]["price"], serde_json::json!(5));
+        assert_eq!(documents[1]["in_stock"], Value::Bool(false));
+    }
+
+    #[test]
+    fn csv_header_with_unrecognized_colon_suffix_keeps_name_verbatim() {
+        let body = "og:title,price\nHello World,5\n";
+
+        let documents = documents_from_csv(body).unwrap();
+
+        assert_eq!(
+            documents[0]["og:title"],
+            Value::String("Hello World".to_string())
+        );
+        assert_eq!(documents[0]["price"], serde_json::json!(5));
+    }
+
+    #[test]
+    fn csv_without_type_hints_sniffs_numbers_and_keeps_strings() {
+        let body = "id,name\n1,Shirt\n2,Mug\n";
+
+        let documents = documents_from_csv(body).unwrap();
+
+        assert_eq!(documents[0]["id"], serde_json::json!(1));
+        assert_eq!(documents[0]["name"], Value::String("Shirt".to_string()));
+    }
+
+    #[test]
+    fn csv_empty_cell_becomes_null() {
+        let body = "name,price:number\nShirt,\n";
+
+        let documents = documents_from_csv(body).unwrap();
+
+        assert_eq!(documents[0]["price"], Value::Null);
+    }
+
+    #[test]
+    fn ndjson_parses_one_object_per_line_and_skips_blank_lines() {
+        let body = "{\"id\": 1}\n\n{\"id\": 2}\n";
+
+        let documents = documents_from_ndjson(body).unwrap();
+
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0]["id"], serde_json::json!(1));
+        assert_eq!(documents[1]["id"], serde_json::json!(2));
+    }
+
+    #[test]
+    fn ndjson_rejects_invalid_json_line() {
+        let body = "{\"id\": 1}\nnot json\n";
+
+        assert!(documents_from_ndjson(body).is_err());
+    }
+}
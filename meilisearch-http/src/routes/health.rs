@@ -1,29 +1,113 @@
+use std::collections::BTreeMap;
+
+use heed::types::{Str, Unit};
+use http::StatusCode;
+use meilisearch_core::update::UpdateStatus;
+use serde::{Deserialize, Serialize};
+use tide::response::IntoResponse;
+use tide::{Context, Response};
+
 use crate::error::{ResponseError, SResult};
 use crate::helpers::tide::ContextExt;
-use crate::helpers::tide::ACL::*;
 use crate::Data;
 
-use heed::types::{Str, Unit};
-use serde::Deserialize;
-use tide::Context;
-
 const UNHEALTHY_KEY: &str = "_is_unhealthy";
 
-pub async fn get_health(ctx: Context<Data>) -> SResult<()> {
-    let db = &ctx.state().db;
+/// `update_id` is a per-index counter in meilisearch-core (each index owns its own update
+/// queue), so these numbers are never comparable, let alone summable, across indexes.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexHealth {
+    pending_updates: usize,
+    processing_updates: usize,
+    last_update_id: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HealthResponse {
+    indexes: BTreeMap<String, IndexHealth>,
+}
+
+/// Reports readiness rather than just liveness: besides the manually-set unhealthy flag, a
+/// node is considered unavailable while its total update queue backlog (summed across indexes)
+/// is above `Opt::max_pending_updates`, so load balancers can stop sending it traffic until it
+/// catches up.
+pub async fn get_health(ctx: Context<Data>) -> SResult<Response> {
+    let data = ctx.state();
+    let db = &data.db;
+
     let reader = db.main_read_txn().map_err(ResponseError::internal)?;
+    let common_store = db.common_store();
+    let manually_unhealthy = common_store
+        .get::<_, Str, Unit>(&reader, UNHEALTHY_KEY)
+        .map_err(ResponseError::internal)?
+        .is_some();
+
+    let update_reader = db.update_read_txn().map_err(ResponseError::internal)?;
+
+    let mut indexes = BTreeMap::new();
+    let mut total_pending = 0;
+    let mut total_processing = 0;
+
+    for index_uid in db.indexes_uids() {
+        let index = match db.open_index(&index_uid) {
+            Some(index) => index,
+            None => continue,
+        };
+
+        let mut pending_updates = 0;
+        let mut processing_updates = 0;
+        let mut last_update_id = None;
+
+        for status in index
+            .all_updates_status(&update_reader)
+            .map_err(ResponseError::internal)?
+        {
+            match status {
+                UpdateStatus::Enqueued(_) => pending_updates += 1,
+                UpdateStatus::Processing(_) => processing_updates += 1,
+                // Only a processed update was actually committed to the index; enqueued,
+                // processing and aborted updates are not, so they must not bump this id.
+                UpdateStatus::Processed(update) => {
+                    last_update_id =
+                        Some(last_update_id.map_or(update.update_id, |max: u64| max.max(update.update_id)));
+                }
+                UpdateStatus::Aborted(_) => (),
+            }
+        }
+
+        total_pending += pending_updates;
+        total_processing += processing_updates;
+
+        indexes.insert(
+            index_uid,
+            IndexHealth {
+                pending_updates,
+                processing_updates,
+                last_update_id,
+            },
+        );
+    }
 
-    let common_store = ctx.state().db.common_store();
+    let body = HealthResponse { indexes };
 
-    if let Ok(Some(_)) = common_store.get::<_, Str, Unit>(&reader, UNHEALTHY_KEY) {
-        return Err(ResponseError::Maintenance);
+    let backlog = total_pending + total_processing;
+    if is_unavailable(manually_unhealthy, backlog, data.options.max_pending_updates) {
+        return Ok(tide::response::json(body)
+            .with_status(StatusCode::SERVICE_UNAVAILABLE)
+            .into_response());
     }
 
-    Ok(())
+    Ok(tide::response::json(body).into_response())
+}
+
+fn is_unavailable(manually_unhealthy: bool, backlog: usize, max_pending_updates: usize) -> bool {
+    manually_unhealthy || backlog > max_pending_updates
 }
 
 pub async fn set_healthy(ctx: Context<Data>) -> SResult<()> {
-    ctx.is_allowed(Admin)?;
+    ctx.is_master()?;
 
     let db = &ctx.state().db;
     let mut writer = db.main_write_txn().map_err(ResponseError::internal)?;
@@ -42,7 +126,7 @@ pub async fn set_healthy(ctx: Context<Data>) -> SResult<()> {
 }
 
 pub async fn set_unhealthy(ctx: Context<Data>) -> SResult<()> {
-    ctx.is_allowed(Admin)?;
+    ctx.is_master()?;
 
     let db = &ctx.state().db;
     let mut writer = db.main_write_txn().map_err(ResponseError::internal)?;
@@ -74,3 +158,28 @@ pub async fn change_healthyness(mut ctx: Context<Data>) -> SResult<()> {
         set_unhealthy(ctx).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn healthy_when_flag_clear_and_backlog_within_threshold() {
+        assert!(!is_unavailable(false, 10, 100));
+    }
+
+    #[test]
+    fn unavailable_when_manually_set_unhealthy() {
+        assert!(is_unavailable(true, 0, 100));
+    }
+
+    #[test]
+    fn unavailable_when_backlog_exceeds_threshold() {
+        assert!(is_unavailable(false, 101, 100));
+    }
+
+    #[test]
+    fn healthy_when_backlog_equals_threshold() {
+        assert!(!is_unavailable(false, 100, 100));
+    }
+}
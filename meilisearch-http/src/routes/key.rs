@@ -1,17 +1,58 @@
+use http::StatusCode;
+use serde::Deserialize;
+use tide::response::IntoResponse;
 use tide::{Context, Response};
 
-use crate::error::SResult;
-use crate::helpers::tide::ContextExt;
-use crate::helpers::tide::ACL::*;
+use crate::error::{ResponseError, SResult};
+use crate::helpers::tide::{all_keys, delete_key, save_key, Action, ApiKey, ContextExt};
 use crate::Data;
 
 pub async fn list(ctx: Context<Data>) -> SResult<Response> {
-    ctx.is_allowed(Admin)?;
+    ctx.is_master()?;
 
-    let keys = &ctx.state().api_keys;
+    let db = &ctx.state().db;
+    let reader = db.main_read_txn().map_err(ResponseError::internal)?;
+    let keys = all_keys(&reader, ctx.state())?;
 
-    Ok(tide::response::json(serde_json::json!({
-        "private": keys.private,
-        "public": keys.public,
-    })))
+    Ok(tide::response::json(keys))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct CreateApiKey {
+    description: Option<String>,
+    actions: Vec<Action>,
+    indexes: Option<Vec<String>>,
+    expires_at: Option<u64>,
+}
+
+pub async fn create(mut ctx: Context<Data>) -> SResult<Response> {
+    ctx.is_master()?;
+
+    let body: CreateApiKey = ctx.body_json().await.map_err(ResponseError::bad_request)?;
+    let key = ApiKey::generate(body.description, body.actions, body.indexes, body.expires_at);
+
+    let db = &ctx.state().db;
+    let mut writer = db.main_write_txn().map_err(ResponseError::internal)?;
+    save_key(ctx.state(), &mut writer, &key)?;
+    writer.commit().map_err(ResponseError::internal)?;
+
+    Ok(tide::response::json(&key)
+        .with_status(StatusCode::CREATED)
+        .into_response())
+}
+
+pub async fn delete(ctx: Context<Data>) -> SResult<Response> {
+    ctx.is_master()?;
+
+    let key = ctx.url_param("key")?;
+
+    let db = &ctx.state().db;
+    let mut writer = db.main_write_txn().map_err(ResponseError::internal)?;
+    delete_key(ctx.state(), &mut writer, &key)?;
+    writer.commit().map_err(ResponseError::internal)?;
+
+    Ok(tide::response::json(serde_json::json!({}))
+        .with_status(StatusCode::NO_CONTENT)
+        .into_response())
 }